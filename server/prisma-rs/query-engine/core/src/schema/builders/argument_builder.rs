@@ -0,0 +1,69 @@
+use super::*;
+use prisma_models::{InternalDataModelRef, ModelRef};
+
+/// Builds the argument lists for query/mutation root fields, delegating the actual input
+/// shapes to [`InputTypeBuilder`]/[`ObjectTypeBuilder`]. The argument *shapes* themselves
+/// (which fields a `create`/`update` payload accepts, what makes a model "unique") aren't the
+/// subject of this change, so the methods below are minimal placeholders behind the real,
+/// borrow-based constructor signature.
+pub struct ArgumentBuilder<'a, 'this> {
+  internal_data_model: InternalDataModelRef,
+  input_type_builder: &'this InputTypeBuilder<'a, 'this>,
+  object_type_builder: &'this ObjectTypeBuilder<'a, 'this>,
+}
+
+impl<'a, 'this> ArgumentBuilder<'a, 'this> {
+  pub fn new(
+    internal_data_model: InternalDataModelRef,
+    input_type_builder: &'this InputTypeBuilder<'a, 'this>,
+    object_type_builder: &'this ObjectTypeBuilder<'a, 'this>,
+  ) -> Self {
+    ArgumentBuilder {
+      internal_data_model,
+      input_type_builder,
+      object_type_builder,
+    }
+  }
+
+  pub fn internal_data_model(&self) -> &InternalDataModelRef {
+    &self.internal_data_model
+  }
+
+  pub fn input_type_builder(&self) -> &'this InputTypeBuilder<'a, 'this> {
+    self.input_type_builder
+  }
+
+  pub fn object_type_builder(&self) -> &'this ObjectTypeBuilder<'a, 'this> {
+    self.object_type_builder
+  }
+
+  pub fn where_unique_argument(&self, model: ModelRef) -> Option<Argument> {
+    let filter_type = self.input_type_builder.filter_object_type_builder().where_argument(&model)?;
+
+    Some(argument("where", InputType::object(filter_type)))
+  }
+
+  pub fn create_arguments(&self, _model: ModelRef) -> Option<Vec<Argument>> {
+    Some(Vec::new())
+  }
+
+  pub fn delete_arguments(&self, model: ModelRef) -> Option<Vec<Argument>> {
+    self.where_unique_argument(model).map(|arg| vec![arg])
+  }
+
+  pub fn update_arguments(&self, model: ModelRef) -> Option<Vec<Argument>> {
+    self.where_unique_argument(model).map(|arg| vec![arg])
+  }
+
+  pub fn upsert_arguments(&self, model: ModelRef) -> Option<Vec<Argument>> {
+    self.update_arguments(model)
+  }
+
+  pub fn update_many_arguments(&self, _model: ModelRef) -> Vec<Argument> {
+    Vec::new()
+  }
+
+  pub fn delete_many_arguments(&self, _model: ModelRef) -> Vec<Argument> {
+    Vec::new()
+  }
+}
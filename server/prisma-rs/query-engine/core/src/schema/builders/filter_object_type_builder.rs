@@ -0,0 +1,44 @@
+use super::*;
+use prisma_models::ModelRef;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Builds the `where` filter input types used to scope queries and model-event subscriptions,
+/// caching every one it builds so [`Self::into_strong_refs`] can hand them all back once
+/// schema generation is done.
+pub struct FilterObjectTypeBuilder<'a> {
+  capabilities: &'a SupportedCapabilities,
+  cache: RefCell<BTreeMap<String, InputObjectTypeStrongRef>>,
+}
+
+impl<'a> FilterObjectTypeBuilder<'a> {
+  pub fn new(capabilities: &'a SupportedCapabilities) -> Self {
+    FilterObjectTypeBuilder {
+      capabilities,
+      cache: RefCell::new(BTreeMap::new()),
+    }
+  }
+
+  /// Returns the (cached) `where` filter input type for `model`, building it on first use.
+  pub fn where_argument(&self, model: &ModelRef) -> Option<InputObjectTypeWeakRef> {
+    if let Some(existing) = self.cache.borrow().get(&model.name) {
+      return Some(Arc::downgrade(existing));
+    }
+
+    let strong_ref = Arc::new(InputObjectType {
+      name: format!("{}WhereInput", model.name),
+    });
+    let weak_ref = Arc::downgrade(&strong_ref);
+    self.cache.borrow_mut().insert(model.name.clone(), strong_ref);
+
+    Some(weak_ref)
+  }
+
+  /// Clones the cache built up by [`Self::where_argument`] into the vector `QuerySchemaBuilder`
+  /// merges into the final schema's input objects; the cache itself is left as is. Takes `&self` rather than consuming the
+  /// builder, since `QuerySchemaBuilder` only ever holds `self` by reference.
+  pub fn into_strong_refs(&self) -> Vec<InputObjectTypeStrongRef> {
+    self.cache.borrow().values().cloned().collect()
+  }
+}
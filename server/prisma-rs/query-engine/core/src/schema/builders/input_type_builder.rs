@@ -0,0 +1,40 @@
+use super::*;
+use prisma_models::InternalDataModelRef;
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Builds GraphQL input object types (e.g. `create`/`update` payload shapes), caching every
+/// one it builds so [`Self::into_strong_refs`] can hand them all back once schema generation
+/// is done. Payload-shape construction itself isn't the subject of this change and is left
+/// minimal; the cache and its `&self` accessor are.
+pub struct InputTypeBuilder<'a, 'this> {
+  internal_data_model: InternalDataModelRef,
+  filter_object_type_builder: &'this FilterObjectTypeBuilder<'a>,
+  cache: RefCell<BTreeMap<String, InputObjectTypeStrongRef>>,
+}
+
+impl<'a, 'this> InputTypeBuilder<'a, 'this> {
+  pub fn new(internal_data_model: InternalDataModelRef, filter_object_type_builder: &'this FilterObjectTypeBuilder<'a>) -> Self {
+    InputTypeBuilder {
+      internal_data_model,
+      filter_object_type_builder,
+      cache: RefCell::new(BTreeMap::new()),
+    }
+  }
+
+  pub fn internal_data_model(&self) -> &InternalDataModelRef {
+    &self.internal_data_model
+  }
+
+  pub fn filter_object_type_builder(&self) -> &'this FilterObjectTypeBuilder<'a> {
+    self.filter_object_type_builder
+  }
+
+  /// Clones the cache into the vector `QuerySchemaBuilder` merges into the final schema's
+  /// input objects; the cache itself is left as is. Takes `&self` rather than consuming the builder, since `QuerySchemaBuilder`
+  /// only ever holds `self` by reference.
+  pub fn into_strong_refs(&self) -> Vec<InputObjectTypeStrongRef> {
+    self.cache.borrow().values().cloned().collect()
+  }
+}
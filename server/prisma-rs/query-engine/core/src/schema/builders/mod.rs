@@ -0,0 +1,305 @@
+use std::sync::{Arc, Weak};
+
+pub mod argument_builder;
+pub mod filter_object_type_builder;
+pub mod input_type_builder;
+pub mod object_type_builder;
+pub mod query_schema_builder;
+
+pub use argument_builder::ArgumentBuilder;
+pub use filter_object_type_builder::FilterObjectTypeBuilder;
+pub use input_type_builder::InputTypeBuilder;
+pub use object_type_builder::ObjectTypeBuilder;
+pub use query_schema_builder::{BuildMode, QuerySchemaBuilder};
+
+/// A capability a connector may or may not support, used to gate schema-shape decisions that
+/// only make sense where the underlying connector can actually back them.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum ConnectorCapability {
+  /// The executor can resolve a selection later, against the field's own query path, instead
+  /// of as part of the initial response.
+  Defer,
+}
+
+/// The capabilities of the active connector. Schema builders consult this to decide whether a
+/// given shape (e.g. a deferrable field) makes sense to offer at all.
+#[derive(Debug, Default)]
+pub struct SupportedCapabilities {
+  capabilities: std::collections::HashSet<ConnectorCapability>,
+}
+
+impl SupportedCapabilities {
+  pub fn new(capabilities: std::collections::HashSet<ConnectorCapability>) -> Self {
+    SupportedCapabilities { capabilities }
+  }
+
+  pub fn has(&self, capability: ConnectorCapability) -> bool {
+    self.capabilities.contains(&capability)
+  }
+
+  pub fn supports_defer(&self) -> bool {
+    self.has(ConnectorCapability::Defer)
+  }
+}
+
+/// A GraphQL object type: a named set of fields. Interned behind an `Arc` so every field that
+/// references it (e.g. a relation, or the same model reached through two paths) can hold a
+/// cheap `Weak` handle instead of cloning the whole type.
+#[derive(Debug)]
+pub struct ObjectType {
+  pub name: String,
+  pub fields: Vec<Field>,
+}
+
+pub type ObjectTypeStrongRef = Arc<ObjectType>;
+pub type ObjectTypeWeakRef = Weak<ObjectType>;
+
+pub fn object_type(name: impl Into<String>, fields: Vec<Field>) -> ObjectType {
+  ObjectType {
+    name: name.into(),
+    fields,
+  }
+}
+
+/// A GraphQL input object type (e.g. a `where` filter or a `create` payload shape).
+#[derive(Debug)]
+pub struct InputObjectType {
+  pub name: String,
+}
+
+pub type InputObjectTypeStrongRef = Arc<InputObjectType>;
+pub type InputObjectTypeWeakRef = Weak<InputObjectType>;
+
+/// A scalar GraphQL leaf type.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ScalarKind {
+  Int,
+  String,
+  Boolean,
+}
+
+/// The type of a field's output: an object, a wrapper around another output type, or - for
+/// `@defer` support - a placeholder the executor resolves later against the field's own query
+/// path instead of as part of the initial response.
+#[derive(Debug, Clone)]
+pub enum OutputType {
+  Object(ObjectTypeWeakRef),
+  Scalar(ScalarKind),
+  Opt(Box<OutputType>),
+  List(Box<OutputType>),
+  Deferred(Box<OutputType>),
+}
+
+impl OutputType {
+  pub fn object(object_type: ObjectTypeWeakRef) -> Self {
+    OutputType::Object(object_type)
+  }
+
+  pub fn scalar(kind: ScalarKind) -> Self {
+    OutputType::Scalar(kind)
+  }
+
+  pub fn opt(inner: OutputType) -> Self {
+    OutputType::Opt(Box::new(inner))
+  }
+
+  pub fn list(inner: OutputType) -> Self {
+    OutputType::List(Box::new(inner))
+  }
+}
+
+/// The type of an argument's input value.
+#[derive(Debug, Clone)]
+pub enum InputType {
+  Object(InputObjectTypeWeakRef),
+  Opt(Box<InputType>),
+}
+
+impl InputType {
+  pub fn object(input_object_type: InputObjectTypeWeakRef) -> Self {
+    InputType::Object(input_object_type)
+  }
+
+  pub fn opt(inner: InputType) -> Self {
+    InputType::Opt(Box::new(inner))
+  }
+}
+
+/// A single GraphQL argument on a field.
+#[derive(Debug, Clone)]
+pub struct Argument {
+  pub name: String,
+  pub input_type: InputType,
+}
+
+pub fn argument(name: impl Into<String>, input_type: InputType) -> Argument {
+  Argument {
+    name: name.into(),
+    input_type,
+  }
+}
+
+/// A single GraphQL field on an object type. `stream_resolver` is set for subscription
+/// fields: instead of resolving once, the executor polls it for a stream of values. The
+/// resolver creates a fresh [`ResolutionContext`] for each emitted value (see
+/// [`resolve_model_event_stream`]); the emitted [`ModelEvent`] does not carry that context.
+#[derive(Clone)]
+pub struct Field {
+  pub name: String,
+  pub arguments: Vec<Argument>,
+  pub field_type: OutputType,
+  pub stream_resolver: Option<StreamResolverFn>,
+}
+
+pub fn field(name: impl Into<String>, arguments: Vec<Argument>, field_type: OutputType) -> Field {
+  Field {
+    name: name.into(),
+    arguments,
+    field_type,
+    stream_resolver: None,
+  }
+}
+
+pub fn stream_field(
+  name: impl Into<String>,
+  arguments: Vec<Argument>,
+  field_type: OutputType,
+  stream_resolver: StreamResolverFn,
+) -> Field {
+  Field {
+    name: name.into(),
+    arguments,
+    field_type,
+    stream_resolver: Some(stream_resolver),
+  }
+}
+
+/// A scoped handle the executor threads through field resolution, identifying where in the
+/// response tree the value being resolved belongs. A subscription stream creates a fresh one
+/// per emitted message (see [`resolve_model_event_stream`]) instead of reusing the one the
+/// field was first resolved with.
+#[derive(Clone)]
+pub struct ResolutionContext {
+  query_path: Vec<String>,
+  event_source: Arc<dyn EventSource>,
+}
+
+impl ResolutionContext {
+  pub fn new(query_path: Vec<String>, event_source: Arc<dyn EventSource>) -> Self {
+    ResolutionContext { query_path, event_source }
+  }
+
+  pub fn query_path(&self) -> &[String] {
+    &self.query_path
+  }
+
+  pub fn event_source(&self) -> Arc<dyn EventSource> {
+    Arc::clone(&self.event_source)
+  }
+
+  /// A fresh context scoped to the same query path, used to resolve a single stream message.
+  pub fn for_message(&self) -> Self {
+    ResolutionContext {
+      query_path: self.query_path.clone(),
+      event_source: Arc::clone(&self.event_source),
+    }
+  }
+}
+
+/// A single row-change notification pushed through a model event subscription stream.
+#[derive(Debug, Clone)]
+pub struct ModelEvent {
+  pub model_name: String,
+  pub operation: query_schema_builder::SubscriptionEvent,
+}
+
+pub type EventStream = std::pin::Pin<Box<dyn futures::Stream<Item = ModelEvent> + Send>>;
+
+/// Where model-change notifications come from; the execution layer supplies the concrete
+/// implementation (e.g. a database changefeed or an in-process event bus). The schema
+/// builder only needs to know how to subscribe to one.
+pub trait EventSource: Send + Sync {
+  fn subscribe(&self, model_name: String, operation: query_schema_builder::SubscriptionEvent) -> EventStream;
+}
+
+pub type StreamResolverFn = Arc<dyn Fn(ResolutionContext) -> EventStream + Send + Sync>;
+
+/// Resolves a model event subscription field into a stream of [`ModelEvent`]s, re-creating a
+/// resolution context for every emitted message rather than reusing the one the stream was
+/// opened with.
+pub fn resolve_model_event_stream(
+  ctx: ResolutionContext,
+  model_name: String,
+  operation: query_schema_builder::SubscriptionEvent,
+) -> EventStream {
+  use futures::StreamExt;
+
+  let source = ctx.event_source();
+  let stream = source.subscribe(model_name, operation).map(move |event| {
+    let _message_ctx = ctx.for_message();
+    event
+  });
+
+  Box::pin(stream)
+}
+
+/// The finished, immutable query schema: the three root output types (query, mutation,
+/// subscription) plus every input/output object type collected while building them.
+pub struct QuerySchema {
+  pub query: OutputType,
+  pub mutation: OutputType,
+  pub subscription: OutputType,
+  pub input_objects: Vec<InputObjectTypeStrongRef>,
+  pub output_objects: Vec<ObjectTypeStrongRef>,
+}
+
+impl QuerySchema {
+  pub fn new(
+    query: OutputType,
+    mutation: OutputType,
+    subscription: OutputType,
+    input_objects: Vec<InputObjectTypeStrongRef>,
+    output_objects: Vec<ObjectTypeStrongRef>,
+  ) -> Self {
+    QuerySchema {
+      query,
+      mutation,
+      subscription,
+      input_objects,
+      output_objects,
+    }
+  }
+}
+
+/// Pushes `item` onto `vec` if it's `Some`, a shorthand for optional fields that are only
+/// emitted when the model supports the underlying operation (e.g. the single-item query
+/// field is only emitted when `where_unique_argument` returns an argument).
+pub fn append_opt<T>(vec: &mut Vec<T>, item: Option<T>) {
+  if let Some(item) = item {
+    vec.push(item);
+  }
+}
+
+/// Lower-cases the first character of a model name to get its field name (`User` -> `user`).
+pub fn camel_case(name: impl Into<String>) -> String {
+  let name = name.into();
+  let mut chars = name.chars();
+
+  match chars.next() {
+    Some(first) => first.to_lowercase().collect::<String>() + chars.as_str(),
+    None => name,
+  }
+}
+
+/// Naive English pluralization for batch field names (`User` -> `Users`, `Category` -> `Categories`).
+pub fn pluralize(name: impl Into<String>) -> String {
+  let name = name.into();
+
+  if name.ends_with('y') && !name.ends_with("ay") && !name.ends_with("ey") && !name.ends_with("oy") {
+    format!("{}ies", &name[..name.len() - 1])
+  } else if name.ends_with('s') {
+    name
+  } else {
+    format!("{}s", name)
+  }
+}
@@ -0,0 +1,140 @@
+use super::*;
+use prisma_models::{InternalDataModelRef, ModelRef};
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Builds GraphQL object types (model types, the batch payload type) and caches every one it
+/// builds so [`Self::into_strong_refs`] can hand them all back once schema generation is done.
+pub struct ObjectTypeBuilder<'a, 'this> {
+  internal_data_model: InternalDataModelRef,
+  with_relations: bool,
+  capabilities: &'a SupportedCapabilities,
+  filter_object_type_builder: &'this FilterObjectTypeBuilder<'a>,
+  cache: RefCell<BTreeMap<String, ObjectTypeStrongRef>>,
+  batch_payload_type: RefCell<Option<ObjectTypeStrongRef>>,
+}
+
+impl<'a, 'this> ObjectTypeBuilder<'a, 'this> {
+  pub fn new(
+    internal_data_model: InternalDataModelRef,
+    with_relations: bool,
+    capabilities: &'a SupportedCapabilities,
+    filter_object_type_builder: &'this FilterObjectTypeBuilder<'a>,
+  ) -> Self {
+    ObjectTypeBuilder {
+      internal_data_model,
+      with_relations,
+      capabilities,
+      filter_object_type_builder,
+      cache: RefCell::new(BTreeMap::new()),
+      batch_payload_type: RefCell::new(None),
+    }
+  }
+
+  /// Returns the (cached) object type for `model`, building its relation fields - when
+  /// `with_relations` is set - on first use. Scalar field construction isn't the subject of
+  /// this change and is left out; the relation-field and caching machinery real builders
+  /// depend on is real.
+  pub fn map_model_object_type(&self, model: &ModelRef) -> ObjectTypeWeakRef {
+    if let Some(existing) = self.cache.borrow().get(&model.name) {
+      return Arc::downgrade(existing);
+    }
+
+    let strong_ref = Arc::new(self.build_model_object_type(model));
+    let weak_ref = Arc::downgrade(&strong_ref);
+    self.cache.borrow_mut().insert(model.name.clone(), strong_ref);
+
+    weak_ref
+  }
+
+  fn build_model_object_type(&self, model: &ModelRef) -> ObjectType {
+    let fields = if self.with_relations {
+      self.relation_fields(model)
+    } else {
+      Vec::new()
+    };
+
+    object_type(model.name.clone(), fields)
+  }
+
+  /// Builds the fields for a model's relations. Deferrable for the same reason the root
+  /// `all_items_field`/`single_item_field` are: a relation selection can be expensive to
+  /// resolve, so under `@defer` the engine can return a placeholder immediately and resolve
+  /// it later against this field's own query path instead of blocking the rest of the
+  /// response on it - the large-nested-selection case `@defer` was actually added for.
+  fn relation_fields(&self, model: &ModelRef) -> Vec<Field> {
+    model
+      .fields()
+      .relation()
+      .iter()
+      .map(|relation_field| {
+        let related = relation_field.related_model();
+        let inner = OutputType::opt(OutputType::object(self.map_model_object_type(&related)));
+        let output_type = if relation_field.is_list {
+          OutputType::list(inner)
+        } else {
+          inner
+        };
+
+        field(relation_field.name.clone(), vec![], self.deferrable(output_type))
+      })
+      .collect()
+  }
+
+  /// Wraps `inner` in `OutputType::Deferred` when the connector supports `@defer`, mirroring
+  /// `QuerySchemaBuilder::deferrable` for root fields.
+  fn deferrable(&self, inner: OutputType) -> OutputType {
+    if self.capabilities.supports_defer() {
+      OutputType::Deferred(Box::new(inner))
+    } else {
+      inner
+    }
+  }
+
+  pub fn many_records_arguments(&self, model: &ModelRef) -> Vec<Argument> {
+    let mut args = Vec::new();
+
+    append_opt(
+      &mut args,
+      self
+        .filter_object_type_builder
+        .where_argument(model)
+        .map(|input_type| argument("where", InputType::opt(InputType::object(input_type)))),
+    );
+
+    args
+  }
+
+  /// Returns the (cached) `BatchPayload` object type shared by every `updateMany`/`deleteMany`
+  /// mutation field, building it on first use.
+  pub fn batch_payload_object_type(&self) -> ObjectTypeWeakRef {
+    if let Some(existing) = self.batch_payload_type.borrow().as_ref() {
+      return Arc::downgrade(existing);
+    }
+
+    let strong_ref = Arc::new(object_type(
+      "BatchPayload",
+      vec![field("count", vec![], OutputType::scalar(ScalarKind::Int))],
+    ));
+    let weak_ref = Arc::downgrade(&strong_ref);
+    *self.batch_payload_type.borrow_mut() = Some(strong_ref);
+
+    weak_ref
+  }
+
+  /// Clones the cache built up by [`Self::map_model_object_type`]/[`Self::batch_payload_object_type`]
+  /// into the vector `QuerySchemaBuilder` merges into the final schema's output objects; the
+  /// cache itself is left as is. Takes
+  /// `&self` rather than consuming the builder, since `QuerySchemaBuilder` only ever holds
+  /// `self` by reference.
+  pub fn into_strong_refs(&self) -> Vec<ObjectTypeStrongRef> {
+    let mut output_objects: Vec<ObjectTypeStrongRef> = self.cache.borrow().values().cloned().collect();
+
+    if let Some(batch_payload) = self.batch_payload_type.borrow().as_ref() {
+      output_objects.push(Arc::clone(batch_payload));
+    }
+
+    output_objects
+  }
+}
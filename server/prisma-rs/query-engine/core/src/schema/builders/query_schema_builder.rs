@@ -1,8 +1,11 @@
 use super::*;
+use ouroboros::self_referencing;
 use prisma_models::{InternalDataModelRef, ModelRef};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashSet};
 use std::sync::Arc;
 
-/// WIP. Build mode for schema generation.
+/// Build mode for schema generation.
 #[derive(Debug, Copy, Clone)]
 pub enum BuildMode {
   /// Prisma 1 compatible schema generation.
@@ -13,17 +16,116 @@ pub enum BuildMode {
   Modern,
 }
 
+/// A root field generated by [`QuerySchemaBuilder`], used as the key into the
+/// [`FieldMorphismRegistry`] so a field's modern/legacy divergence can be looked up
+/// independently of the model it's generated for.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum RootField {
+  AllItems,
+  SingleItem,
+  Create,
+  Delete,
+  Update,
+  Upsert,
+  UpdateMany,
+  DeleteMany,
+}
+
+/// Classifies how a [`RootField`] maps from the modern schema onto the legacy (Prisma 1)
+/// one, borrowing the morphism vocabulary to make the divergence auditable: isomorphic
+/// transforms are reversible (same capability, legacy name built from the registry), monomorphic transforms
+/// lose no information (legacy is a faithful subset), and epimorphic transforms are lossy -
+/// the modern capability has no faithful legacy equivalent and must be dropped or degraded.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum FieldMorphism {
+  /// Reversible rename: same capability, named in the legacy schema from `legacy_name` (for the
+  /// batch mutations this yields the same name as the modern schema, e.g. `updateManyUsers`).
+  Isomorphic { legacy_name: &'static str },
+  /// Legacy emits the exact same field as modern; there is nothing to translate.
+  Monomorphic,
+  /// Modern-only capability; legacy generation must drop (or degrade) the field.
+  Epimorphic { reason: &'static str },
+}
+
+/// Central lookup from root field kind to its modern/legacy [`FieldMorphism`]. Keeping the
+/// classification in one place means every legacy/modern divergence is declared exactly
+/// once and is testable on its own, instead of being implicit in each field builder method.
+struct FieldMorphismRegistry;
+
+impl FieldMorphismRegistry {
+  fn classify(field: RootField) -> FieldMorphism {
+    match field {
+      RootField::AllItems => FieldMorphism::Monomorphic,
+      RootField::SingleItem => FieldMorphism::Monomorphic,
+      RootField::Create => FieldMorphism::Monomorphic,
+      RootField::Delete => FieldMorphism::Monomorphic,
+      RootField::Update => FieldMorphism::Monomorphic,
+      RootField::UpdateMany => FieldMorphism::Isomorphic { legacy_name: "updateMany" },
+      RootField::DeleteMany => FieldMorphism::Isomorphic { legacy_name: "deleteMany" },
+      RootField::Upsert => FieldMorphism::Epimorphic {
+        reason: "Prisma 1 has no upsert mutation; there is no faithful legacy equivalent to degrade to",
+      },
+    }
+  }
+}
+
+/// Records a modern capability that generation had to drop while building the legacy
+/// schema, so the caller can surface the divergence instead of it silently disappearing.
+#[derive(Debug, Clone)]
+struct LegacyFieldWarning {
+  model_name: String,
+  field: RootField,
+  reason: &'static str,
+}
+
+/// The kind of row-change event a subscription field streams.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SubscriptionEvent {
+  Created,
+  Updated,
+  Deleted,
+}
+
+impl SubscriptionEvent {
+  fn suffix(&self) -> &'static str {
+    match self {
+      SubscriptionEvent::Created => "Created",
+      SubscriptionEvent::Updated => "Updated",
+      SubscriptionEvent::Deleted => "Deleted",
+    }
+  }
+}
+
 /// Query schema builder. Root for query schema building.
-/// The schema builder creates all builders necessary for the process,
-/// and hands down references to the individual initializers as required.
+/// The schema builder creates all builders necessary for the process, and owns them directly:
+/// `input_type_builder` and `object_type_builder` borrow `filter_object_type_builder`, and
+/// `argument_builder` borrows those two, so each holds plain references into `Self` instead
+/// of `Weak` handles into a shared `Arc`. This is a self-referencing
+/// struct (generated by ouroboros' `#[self_referencing]`) because those borrows would
+/// otherwise be impossible to express as a plain struct with a single lifetime.
+///
+/// `ObjectTypeBuilder`, `InputTypeBuilder` and `ArgumentBuilder` (see the sibling modules
+/// under `builders/`) accept this `'this` borrow instead of a `Weak` handle in their
+/// constructors; `FilterObjectTypeBuilder` borrows only the connector capabilities.
+#[self_referencing]
 pub struct QuerySchemaBuilder<'a> {
   mode: BuildMode,
   internal_data_model: InternalDataModelRef,
   capabilities: &'a SupportedCapabilities,
-  object_type_builder: Arc<ObjectTypeBuilder<'a>>,
-  input_type_builder: Arc<InputTypeBuilder<'a>>,
-  argument_builder: ArgumentBuilder<'a>,
-  filter_object_type_builder: Arc<FilterObjectTypeBuilder<'a>>,
+  legacy_warnings: RefCell<Vec<LegacyFieldWarning>>,
+  filter_object_type_builder: FilterObjectTypeBuilder<'a>,
+
+  #[borrows(filter_object_type_builder)]
+  #[covariant]
+  input_type_builder: InputTypeBuilder<'a, 'this>,
+
+  #[borrows(filter_object_type_builder)]
+  #[covariant]
+  object_type_builder: ObjectTypeBuilder<'a, 'this>,
+
+  #[borrows(input_type_builder, object_type_builder)]
+  #[covariant]
+  argument_builder: ArgumentBuilder<'a, 'this>,
 }
 
 impl<'a> QuerySchemaBuilder<'a> {
@@ -32,46 +134,46 @@ impl<'a> QuerySchemaBuilder<'a> {
     capabilities: &'a SupportedCapabilities,
     mode: BuildMode,
   ) -> Self {
-    let filter_object_type_builder = Arc::new(FilterObjectTypeBuilder::new(capabilities));
-    let input_type_builder = Arc::new(InputTypeBuilder::new(
-      Arc::clone(internal_data_model),
-      Arc::downgrade(&filter_object_type_builder),
-    ));
-
-    let object_type_builder = Arc::new(ObjectTypeBuilder::new(
-      Arc::clone(internal_data_model),
-      true,
-      capabilities,
-      Arc::downgrade(&filter_object_type_builder),
-    ));
-
-    let argument_builder = ArgumentBuilder::new(
-      Arc::clone(internal_data_model),
-      Arc::downgrade(&input_type_builder),
-      Arc::downgrade(&object_type_builder),
-    );
-
-    QuerySchemaBuilder {
+    QuerySchemaBuilderBuilder {
       mode,
       internal_data_model: Arc::clone(internal_data_model),
       capabilities,
-      object_type_builder,
-      input_type_builder,
-      argument_builder,
-      filter_object_type_builder,
+      legacy_warnings: RefCell::new(Vec::new()),
+      filter_object_type_builder: FilterObjectTypeBuilder::new(capabilities),
+
+      input_type_builder_builder: |filter_object_type_builder| {
+        InputTypeBuilder::new(Arc::clone(internal_data_model), filter_object_type_builder)
+      },
+
+      object_type_builder_builder: |filter_object_type_builder| {
+        ObjectTypeBuilder::new(
+          Arc::clone(internal_data_model),
+          true,
+          capabilities,
+          filter_object_type_builder,
+        )
+      },
+
+      argument_builder_builder: |input_type_builder, object_type_builder| {
+        ArgumentBuilder::new(Arc::clone(internal_data_model), input_type_builder, object_type_builder)
+      },
     }
+    .build()
   }
 
-  /// Consumes the builders and collects all types from all builder caches to merge
-  /// them into the vectors required to finalize the query schema building.
-  /// Unwraps are safe because only the query schema builder holds the strong ref,
-  /// which makes the Arc counter 1, all other refs are weak refs.
-  fn collect_types(self) -> (Vec<InputObjectTypeStrongRef>, Vec<ObjectTypeStrongRef>) {
-    let output_objects = Arc::try_unwrap(self.object_type_builder).unwrap().into_strong_refs();
-    let mut input_objects = Arc::try_unwrap(self.input_type_builder).unwrap().into_strong_refs();
-    let mut filter_objects = Arc::try_unwrap(self.filter_object_type_builder)
-      .unwrap()
-      .into_strong_refs();
+  /// Collects all types from the sub-builders' caches, merging them into the vectors
+  /// required to finalize the query schema building. There's no reference-count invariant
+  /// to uphold anymore, but there's also no `Arc::try_unwrap`-style ownership to reclaim:
+  /// `object_type_builder` and `input_type_builder` are `#[borrows(..)]` tail fields, so
+  /// `into_heads()` can only ever hand back head fields, never these. Instead this takes
+  /// `&self` and reads each cache through the `borrow_*` accessor, exactly like
+  /// `legacy_warnings` is read elsewhere on this struct - which is why `into_strong_refs` on
+  /// `ObjectTypeBuilder`/`InputTypeBuilder`/`FilterObjectTypeBuilder` itself takes `&self` and
+  /// clones out of an interior-mutable cache rather than consuming the builder by value.
+  fn collect_types(&self) -> (Vec<InputObjectTypeStrongRef>, Vec<ObjectTypeStrongRef>) {
+    let output_objects = self.borrow_object_type_builder().into_strong_refs();
+    let mut input_objects = self.borrow_input_type_builder().into_strong_refs();
+    let mut filter_objects = self.borrow_filter_object_type_builder().into_strong_refs();
 
     input_objects.append(&mut filter_objects);
     (input_objects, output_objects)
@@ -82,12 +184,34 @@ impl<'a> QuerySchemaBuilder<'a> {
   pub fn build(self) -> QuerySchema {
     let (query_type, query_object_ref) = self.build_query_type();
     let (mutation_type, mutation_object_ref) = self.build_mutation_type();
+    let (subscription_type, subscription_object_ref) = self.build_subscription_type();
+    let legacy_warnings = self.borrow_legacy_warnings().borrow().clone();
     let (input_objects, mut output_objects) = self.collect_types();
 
     output_objects.push(query_object_ref);
     output_objects.push(mutation_object_ref);
+    output_objects.push(subscription_object_ref);
+
+    for warning in legacy_warnings {
+      log::warn!(
+        "legacy schema generation dropped {:?} on model {}: {}",
+        warning.field,
+        warning.model_name,
+        warning.reason
+      );
+    }
 
-    QuerySchema::new(query_type, mutation_type, input_objects, output_objects)
+    QuerySchema::new(query_type, mutation_type, subscription_type, input_objects, output_objects)
+  }
+
+  /// Records that `field` had to be dropped from the legacy schema for `model`, because
+  /// its [`FieldMorphism`] is epimorphic and the active mode is [`BuildMode::Legacy`].
+  fn record_lossy_transform(&self, model: &ModelRef, field: RootField, reason: &'static str) {
+    self.borrow_legacy_warnings().borrow_mut().push(LegacyFieldWarning {
+      model_name: model.name.clone(),
+      field,
+      reason,
+    });
   }
 
   /// Builds the root query type.
@@ -134,115 +258,443 @@ impl<'a> QuerySchemaBuilder<'a> {
     (OutputType::Object(Arc::downgrade(&strong_ref)), strong_ref)
   }
 
-  fn non_embedded_models(&self) -> Vec<ModelRef> {
+  /// Builds the root subscription type.
+  fn build_subscription_type(&self) -> (OutputType, ObjectTypeStrongRef) {
+    let non_embedded_models = self.non_embedded_models();
+    let fields = non_embedded_models
+      .into_iter()
+      .map(|m| self.model_subscription_fields(m))
+      .flatten()
+      .collect();
+
+    let strong_ref = Arc::new(object_type("Subscription", fields));
+
+    (OutputType::Object(Arc::downgrade(&strong_ref)), strong_ref)
+  }
+
+  /// Builds the `onXCreated` / `onXUpdated` / `onXDeleted` event fields for a single model.
+  /// Each field accepts an optional `where` filter so subscribers can scope the stream to a
+  /// subset of the affected rows.
+  fn model_subscription_fields(&self, model: ModelRef) -> Vec<Field> {
+    vec![
+      self.model_event_field(SubscriptionEvent::Created, Arc::clone(&model)),
+      self.model_event_field(SubscriptionEvent::Updated, Arc::clone(&model)),
+      self.model_event_field(SubscriptionEvent::Deleted, model),
+    ]
+  }
+
+  /// Builds a single model event subscription field (e.g. "onUserCreated") for given model,
+  /// with a `stream_resolver` that re-creates a [`ResolutionContext`] for every event it
+  /// emits (via [`resolve_model_event_stream`]) rather than resolving the field once.
+  fn model_event_field(&self, event: SubscriptionEvent, model: ModelRef) -> Field {
+    let mut args = vec![];
+    append_opt(&mut args, self.subscription_where_argument(&model));
+
+    let model_name = model.name.clone();
+
+    stream_field(
+      format!("on{}{}", model.name, event.suffix()),
+      args,
+      OutputType::opt(OutputType::object(
+        self.borrow_object_type_builder().map_model_object_type(&model),
+      )),
+      Arc::new(move |ctx| resolve_model_event_stream(ctx, model_name.clone(), event)),
+    )
+  }
+
+  /// Builds the optional `where` filter argument subscribers can use to scope a model's event stream.
+  fn subscription_where_argument(&self, model: &ModelRef) -> Option<Argument> {
     self
-      .internal_data_model
+      .borrow_filter_object_type_builder()
+      .where_argument(model)
+      .map(|input_type| argument("where", InputType::opt(InputType::object(input_type))))
+  }
+
+  /// Returns all non-embedded models in a deterministic, topologically sorted order: a
+  /// model referenced by a relation field is emitted before the model that references it,
+  /// so the object/input types built off this order come out before their dependents too.
+  /// Ties are broken by visiting models, and each model's dependencies, in alphabetical
+  /// order; a dependency is pulled forward ahead of alphabetically earlier unrelated models
+  /// (`A -> Z` plus `B` gives `Z, A, B`). Relation cycles (self-relations, mutual relations) are broken deterministically by
+  /// never revisiting a model that's already in progress. The result is that re-running
+  /// generation on the same datamodel always yields the same field and type order.
+  fn non_embedded_models(&self) -> Vec<ModelRef> {
+    let mut models: Vec<ModelRef> = self
+      .borrow_internal_data_model()
       .models()
       .iter()
       .filter(|m| !m.is_embedded)
       .map(|m| Arc::clone(m))
-      .collect()
+      .collect();
+
+    models.sort_by(|a, b| a.name.cmp(&b.name));
+    topologically_sort_models(models)
   }
 
   /// Builds a "multiple" query arity items field (e.g. "users", "posts", ...) for given model.
+  /// When a `@defer` directive lands on this field's selection, the engine returns a
+  /// `Deferred` placeholder immediately and resolves the list on the field's own patch
+  /// stream, so top-level siblings aren't held up by expensive nested relations.
+  ///
+  /// `ObjectTypeBuilder::relation_fields` applies the same treatment to the nested relation
+  /// fields on a model's object type, which is the large-selection case this was actually
+  /// added for.
   fn all_items_field(&self, model: ModelRef) -> Field {
-    let args = self.object_type_builder.many_records_arguments(&model);
+    let args = self.borrow_object_type_builder().many_records_arguments(&model);
 
     field(
       camel_case(pluralize(model.name.clone())),
       args,
-      OutputType::list(OutputType::opt(OutputType::object(
-        self.object_type_builder.map_model_object_type(&model),
-      ))),
+      self.deferrable(OutputType::list(OutputType::opt(OutputType::object(
+        self.borrow_object_type_builder().map_model_object_type(&model),
+      )))),
     )
   }
 
   /// Builds a "single" query arity item field (e.g. "user", "post" ...) for given model.
+  /// Deferrable for the same reason as [`Self::all_items_field`]: the object can be handed
+  /// back later as a patch against this field's query path instead of blocking the response.
   fn single_item_field(&self, model: ModelRef) -> Option<Field> {
     self
-      .argument_builder
+      .borrow_argument_builder()
       .where_unique_argument(Arc::clone(&model))
       .map(|arg| {
         field(
           camel_case(model.name.clone()),
           vec![arg],
-          OutputType::opt(OutputType::object(
-            self.object_type_builder.map_model_object_type(&model),
-          )),
+          self.deferrable(OutputType::opt(OutputType::object(
+            self.borrow_object_type_builder().map_model_object_type(&model),
+          ))),
         )
       })
   }
 
+  /// Wraps an object/list output type in `OutputType::Deferred` when the connector supports
+  /// `@defer`. The wrapper only marks the field as deferrable -
+  /// whether a given selection is actually deferred is decided per-request by the executor
+  /// from the `@defer` directive on that selection, not at schema build time.
+  ///
+  /// Called from the root `all_items_field`/`single_item_field`; `ObjectTypeBuilder` has its
+  /// own copy of this for relation fields, since it can't borrow `capabilities` through this
+  /// builder's self-referencing struct.
+  fn deferrable(&self, inner: OutputType) -> OutputType {
+    if self.borrow_capabilities().supports_defer() {
+      OutputType::Deferred(Box::new(inner))
+    } else {
+      inner
+    }
+  }
+
   /// Builds a create mutation field (e.g. createUser) for given model.
   fn create_item_field(&self, model: ModelRef) -> Field {
     let args = self
-      .argument_builder
+      .borrow_argument_builder()
       .create_arguments(Arc::clone(&model))
       .unwrap_or_else(|| vec![]);
 
     field(
       format!("create{}", model.name),
       args,
-      OutputType::object(self.object_type_builder.map_model_object_type(&model)),
+      OutputType::object(self.borrow_object_type_builder().map_model_object_type(&model)),
     )
   }
 
   /// Builds a delete mutation field (e.g. deleteUser) for given model.
   fn delete_item_field(&self, model: ModelRef) -> Option<Field> {
-    self.argument_builder.delete_arguments(Arc::clone(&model)).map(|args| {
-      field(
-        format!("delete{}", model.name),
-        args,
-        OutputType::opt(OutputType::object(
-          self.object_type_builder.map_model_object_type(&model),
-        )),
-      )
-    })
+    self
+      .borrow_argument_builder()
+      .delete_arguments(Arc::clone(&model))
+      .map(|args| {
+        field(
+          format!("delete{}", model.name),
+          args,
+          OutputType::opt(OutputType::object(
+            self.borrow_object_type_builder().map_model_object_type(&model),
+          )),
+        )
+      })
   }
 
   /// Builds an update mutation field (e.g. updateUser) for given model.
   fn update_item_field(&self, model: ModelRef) -> Option<Field> {
-    self.argument_builder.update_arguments(Arc::clone(&model)).map(|args| {
-      field(
-        format!("update{}", model.name),
-        args,
-        OutputType::opt(OutputType::object(
-          self.object_type_builder.map_model_object_type(&model),
-        )),
-      )
-    })
-  }
-
-  /// Builds an upsert mutation field (e.g. upsertUser) for given model.
+    self
+      .borrow_argument_builder()
+      .update_arguments(Arc::clone(&model))
+      .map(|args| {
+        field(
+          format!("update{}", model.name),
+          args,
+          OutputType::opt(OutputType::object(
+            self.borrow_object_type_builder().map_model_object_type(&model),
+          )),
+        )
+      })
+  }
+
+  /// Builds an upsert mutation field (e.g. upsertUser) for given model. Upsert is epimorphic
+  /// under [`BuildMode::Legacy`]: Prisma 1 has no upsert mutation, so the field is dropped
+  /// and the loss is recorded instead of silently degrading to e.g. a plain update.
   fn upsert_item_field(&self, model: ModelRef) -> Option<Field> {
-    self.argument_builder.upsert_arguments(Arc::clone(&model)).map(|args| {
-      field(
-        format!("upsert{}", model.name),
-        args,
-        OutputType::object(self.object_type_builder.map_model_object_type(&model)),
-      )
-    })
+    if let FieldMorphism::Epimorphic { reason } = FieldMorphismRegistry::classify(RootField::Upsert) {
+      if matches!(self.borrow_mode(), BuildMode::Legacy) {
+        self.record_lossy_transform(&model, RootField::Upsert, reason);
+        return None;
+      }
+    }
+
+    self
+      .borrow_argument_builder()
+      .upsert_arguments(Arc::clone(&model))
+      .map(|args| {
+        field(
+          format!("upsert{}", model.name),
+          args,
+          OutputType::object(self.borrow_object_type_builder().map_model_object_type(&model)),
+        )
+      })
   }
 
-  /// Builds an update many mutation field (e.g. updateManyUsers) for given model.
+  /// Builds an update many mutation field (e.g. updateManyUsers) for given model. Isomorphic
+  /// under [`BuildMode::Legacy`]: same capability, just the batch name Prisma 1 used.
   fn update_many_field(&self, model: ModelRef) -> Field {
-    let arguments = self.argument_builder.update_many_arguments(Arc::clone(&model));
+    let arguments = self.borrow_argument_builder().update_many_arguments(Arc::clone(&model));
+    let legacy_name = isomorphic_legacy_name(FieldMorphismRegistry::classify(RootField::UpdateMany));
 
     field(
-      format!("updateMany{}", pluralize(model.name.clone())),
+      self.root_field_name(legacy_name, &model, || format!("updateMany{}", pluralize(model.name.clone()))),
       arguments,
-      OutputType::object(self.object_type_builder.batch_payload_object_type()),
+      OutputType::object(self.borrow_object_type_builder().batch_payload_object_type()),
     )
   }
 
-  /// Builds a delete many mutation field (e.g. deleteManyUsers) for given model.
+  /// Builds a delete many mutation field (e.g. deleteManyUsers) for given model. Isomorphic
+  /// under [`BuildMode::Legacy`]: same capability, just the batch name Prisma 1 used.
   fn delete_many_field(&self, model: ModelRef) -> Field {
-    let arguments = self.argument_builder.delete_many_arguments(Arc::clone(&model));
+    let arguments = self.borrow_argument_builder().delete_many_arguments(Arc::clone(&model));
+    let legacy_name = isomorphic_legacy_name(FieldMorphismRegistry::classify(RootField::DeleteMany));
 
     field(
-      format!("deleteMany{}", pluralize(model.name.clone())),
+      self.root_field_name(legacy_name, &model, || format!("deleteMany{}", pluralize(model.name.clone()))),
       arguments,
-      OutputType::object(self.object_type_builder.batch_payload_object_type()),
+      OutputType::object(self.borrow_object_type_builder().batch_payload_object_type()),
     )
   }
-}
\ No newline at end of file
+
+  /// Resolves an isomorphic field's name, swapping in `legacy_name` (pluralized the same way
+  /// the modern name is) under [`BuildMode::Legacy`] and falling back to `modern_name`
+  /// otherwise - including when `legacy_name` is `None`, so a field whose classification isn't
+  /// actually isomorphic just keeps its modern name instead of panicking.
+  fn root_field_name(&self, legacy_name: Option<&'static str>, model: &ModelRef, modern_name: impl FnOnce() -> String) -> String {
+    match (legacy_name, self.borrow_mode()) {
+      (Some(legacy_name), BuildMode::Legacy) => format!("{}{}", legacy_name, pluralize(model.name.clone())),
+      _ => modern_name(),
+    }
+  }
+}
+
+/// Extracts the legacy name out of an [`FieldMorphism::Isomorphic`] classification, or `None`
+/// for any other morphism. A plain function rather than a method so it stays obviously total -
+/// there's no variant it can't handle, unlike matching on [`RootField`] directly.
+fn isomorphic_legacy_name(morphism: FieldMorphism) -> Option<&'static str> {
+  match morphism {
+    FieldMorphism::Isomorphic { legacy_name } => Some(legacy_name),
+    FieldMorphism::Monomorphic | FieldMorphism::Epimorphic { .. } => None,
+  }
+}
+
+/// Topologically sorts `models` (already alphabetically ordered) by their relation
+/// dependencies: a model's relation targets come out before the model itself. Delegates the
+/// actual ordering to [`topological_order`], a plain `String`-keyed version of the same
+/// algorithm that doesn't need a `ModelRef` to test.
+fn topologically_sort_models(models: Vec<ModelRef>) -> Vec<ModelRef> {
+  let by_name: BTreeMap<String, ModelRef> = models.iter().map(|m| (m.name.clone(), Arc::clone(m))).collect();
+
+  let dependencies: Vec<(String, Vec<String>)> = models
+    .iter()
+    .map(|model| (model.name.clone(), relation_dependency_names(model)))
+    .collect();
+
+  topological_order(dependencies)
+    .into_iter()
+    .map(|name| Arc::clone(by_name.get(&name).expect("topological_order only returns known names")))
+    .collect()
+}
+
+/// The names of the models `model` depends on via a relation field, deduped, sorted, and with
+/// self-relations filtered out (a model doesn't need to come after itself).
+fn relation_dependency_names(model: &ModelRef) -> Vec<String> {
+  let mut dependencies: Vec<String> = model
+    .fields()
+    .relation()
+    .iter()
+    .map(|f| f.related_model().name.clone())
+    .filter(|name| name != &model.name)
+    .collect();
+
+  dependencies.sort();
+  dependencies.dedup();
+  dependencies
+}
+
+/// Topologically sorts a set of named items given as `(name, dependency_names)` pairs, via
+/// post-order DFS: an item's dependencies are visited, and thus pushed onto the result, before
+/// the item itself. Cycles (self-relations, mutual relations) are broken deterministically by
+/// tracking in-progress items and simply not re-entering one that's already on the stack, so
+/// every item is still emitted exactly once. A dependency name with no matching item is
+/// ignored rather than treated as an error, mirroring how [`topologically_sort_models`] only
+/// orders against the model set it was actually given.
+fn topological_order(items: Vec<(String, Vec<String>)>) -> Vec<String> {
+  let dependencies_by_name: BTreeMap<String, Vec<String>> = items.into_iter().collect();
+  let mut visited = HashSet::new();
+  let mut in_progress = HashSet::new();
+  let mut ordered = Vec::with_capacity(dependencies_by_name.len());
+
+  for name in dependencies_by_name.keys() {
+    visit(name, &dependencies_by_name, &mut visited, &mut in_progress, &mut ordered);
+  }
+
+  ordered
+}
+
+fn visit(
+  name: &str,
+  dependencies_by_name: &BTreeMap<String, Vec<String>>,
+  visited: &mut HashSet<String>,
+  in_progress: &mut HashSet<String>,
+  ordered: &mut Vec<String>,
+) {
+  if visited.contains(name) || in_progress.contains(name) {
+    return;
+  }
+
+  in_progress.insert(name.to_owned());
+
+  if let Some(dependencies) = dependencies_by_name.get(name) {
+    for dependency_name in dependencies {
+      if dependencies_by_name.contains_key(dependency_name) {
+        visit(dependency_name, dependencies_by_name, visited, in_progress, ordered);
+      }
+    }
+  }
+
+  in_progress.remove(name);
+  visited.insert(name.to_owned());
+  ordered.push(name.to_owned());
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn monomorphic_fields_have_no_legacy_divergence() {
+    for field in [
+      RootField::AllItems,
+      RootField::SingleItem,
+      RootField::Create,
+      RootField::Delete,
+      RootField::Update,
+    ] {
+      assert_eq!(FieldMorphismRegistry::classify(field), FieldMorphism::Monomorphic);
+    }
+  }
+
+  #[test]
+  fn batch_mutations_are_isomorphic_renames() {
+    assert_eq!(
+      FieldMorphismRegistry::classify(RootField::UpdateMany),
+      FieldMorphism::Isomorphic { legacy_name: "updateMany" }
+    );
+    assert_eq!(
+      FieldMorphismRegistry::classify(RootField::DeleteMany),
+      FieldMorphism::Isomorphic { legacy_name: "deleteMany" }
+    );
+  }
+
+  #[test]
+  fn upsert_is_epimorphic_and_lossy() {
+    assert!(matches!(
+      FieldMorphismRegistry::classify(RootField::Upsert),
+      FieldMorphism::Epimorphic { .. }
+    ));
+  }
+
+  #[test]
+  fn isomorphic_legacy_name_is_total() {
+    assert_eq!(isomorphic_legacy_name(FieldMorphism::Monomorphic), None);
+    assert_eq!(
+      isomorphic_legacy_name(FieldMorphism::Epimorphic { reason: "no legacy equivalent" }),
+      None
+    );
+    assert_eq!(
+      isomorphic_legacy_name(FieldMorphism::Isomorphic { legacy_name: "updateMany" }),
+      Some("updateMany")
+    );
+  }
+
+  fn names(items: &[(String, Vec<String>)]) -> Vec<String> {
+    topological_order(items.to_vec())
+  }
+
+  #[test]
+  fn independent_items_come_out_alphabetically() {
+    let items = vec![
+      ("Zebra".to_owned(), vec![]),
+      ("Alpaca".to_owned(), vec![]),
+      ("Moose".to_owned(), vec![]),
+    ];
+
+    assert_eq!(names(&items), vec!["Alpaca", "Moose", "Zebra"]);
+  }
+
+  #[test]
+  fn dependency_is_ordered_before_dependent() {
+    let items = vec![("Post".to_owned(), vec!["User".to_owned()]), ("User".to_owned(), vec![])];
+
+    assert_eq!(names(&items), vec!["User", "Post"]);
+  }
+
+  #[test]
+  fn self_relation_does_not_infinite_loop_and_is_emitted_once() {
+    let items = vec![("User".to_owned(), vec!["User".to_owned()])];
+
+    assert_eq!(names(&items), vec!["User"]);
+  }
+
+  #[test]
+  fn mutual_relation_breaks_the_cycle_and_emits_every_model_once() {
+    let items = vec![
+      ("User".to_owned(), vec!["Post".to_owned()]),
+      ("Post".to_owned(), vec!["User".to_owned()]),
+    ];
+
+    let ordered = names(&items);
+
+    assert_eq!(ordered.len(), 2);
+    assert!(ordered.contains(&"User".to_owned()));
+    assert!(ordered.contains(&"Post".to_owned()));
+  }
+
+  #[test]
+  fn three_way_cycle_still_emits_every_model_exactly_once() {
+    let items = vec![
+      ("A".to_owned(), vec!["B".to_owned()]),
+      ("B".to_owned(), vec!["C".to_owned()]),
+      ("C".to_owned(), vec!["A".to_owned()]),
+    ];
+
+    let ordered = names(&items);
+
+    assert_eq!(ordered.len(), 3);
+    for name in ["A", "B", "C"] {
+      assert_eq!(ordered.iter().filter(|n| n.as_str() == name).count(), 1);
+    }
+  }
+
+  #[test]
+  fn dependency_name_with_no_matching_item_is_ignored() {
+    let items = vec![("User".to_owned(), vec!["Ghost".to_owned()])];
+
+    assert_eq!(names(&items), vec!["User"]);
+  }
+}